@@ -0,0 +1,235 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! High-level, run-oriented Golomb encoding of monotonically increasing
+//! sequences.
+//!
+//! [`GolombRunWriter`]/[`GolombRunReader`] wrap any
+//! [`GolombWrite`]/[`GolombRead`] and delta-code a whole sequence of
+//! values against the previous one, amortizing the per-call setup that
+//! [`write_golomb`](GolombWrite::write_golomb) would otherwise redo on
+//! every call. The modulo `b`, `log2b = ⌈log2(b)⌉`, and
+//! `max_little_value = (1 << log2b) - b` are computed once when the
+//! writer/reader is created, mirroring the `GolombBitStreamWriter` of
+//! [Project Thrill](https://github.com/thrill/thrill).
+//!
+//! A dedicated escape code — a unary quotient of zero followed by the
+//! sentinel remainder zero, a combination no pushed value produces since
+//! every delta is shifted by one — represents
+//! a configurable "no value / end of run" marker, so the same stream
+//! can carry structure (run boundaries) alongside payload (posting
+//! lists, set differences, ...) without a side channel.
+
+use super::golomb::{GolombRead, GolombWrite};
+use crate::traits::*;
+
+/// A streaming, delta-coding Golomb writer for monotonically increasing
+/// sequences, with a reserved escape code for an "end of run" marker.
+pub struct GolombRunWriter<E: Endianness, B: GolombWrite<E>> {
+    bits: B,
+    b: u64,
+    log2b: u64,
+    max_little_value: u64,
+    last: u64,
+    _marker: core::marker::PhantomData<E>,
+}
+
+impl<E: Endianness, B: GolombWrite<E>> GolombRunWriter<E, B> {
+    /// Creates a new writer with modulo `b`, wrapping `bits`.
+    pub fn new(bits: B, b: u64) -> Self {
+        let log2b = (u64::BITS - (b - 1).leading_zeros()) as u64;
+        Self {
+            bits,
+            b,
+            log2b,
+            max_little_value: (1 << log2b) - b,
+            last: 0,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Pushes `value` onto the run, delta-coding it against the
+    /// previously pushed value (or zero, for the first value).
+    ///
+    /// Values must be pushed in non-decreasing order.
+    pub fn push(&mut self, value: u64) -> Result<usize, B::Error> {
+        let delta = value - self.last;
+        self.last = value;
+        // Shift by one to keep a delta of zero free for the escape code.
+        let coded = delta + 1;
+        Ok(self.bits.write_unary(coded / self.b)? + self.write_little(coded % self.b)?)
+    }
+
+    /// Writes the reserved "no value / end of run" escape marker: a
+    /// unary quotient of zero followed by the sentinel remainder `0`,
+    /// which [`push`](Self::push) never produces since every delta is
+    /// shifted by one.
+    pub fn push_escape(&mut self) -> Result<usize, B::Error> {
+        Ok(self.bits.write_unary(0)? + self.write_little(0)?)
+    }
+
+    /// Writes `r` (with `0 <= r < b`) using the precomputed
+    /// [`log2b`](Self) and [`max_little_value`](Self) instead of
+    /// recomputing them, as [`write_minimal_binary`](super::minimal_binary::MinimalBinaryWrite::write_minimal_binary) would on every call.
+    ///
+    /// `b == 1` (`log2b == 0`) needs no bits at all: `r` is always `0`.
+    /// Otherwise the long codeword, when taken, is written as two
+    /// separate `write_bits` calls — the high `log2b - 1` bits then the
+    /// low bit — rather than as a single `log2b`-bit value, so that the
+    /// matching [`GolombRunReader::read_little`] round-trips regardless
+    /// of [`Endianness`].
+    #[inline]
+    fn write_little(&mut self, r: u64) -> Result<usize, B::Error> {
+        if self.log2b == 0 {
+            return Ok(0);
+        }
+        if r < self.max_little_value {
+            self.bits.write_bits(r, (self.log2b - 1) as usize)
+        } else {
+            let long = r + self.max_little_value;
+            Ok(self.bits.write_bits(long >> 1, (self.log2b - 1) as usize)?
+                + self.bits.write_bits(long & 1, 1)?)
+        }
+    }
+
+    /// Returns the wrapped writer, consuming `self`.
+    pub fn into_inner(self) -> B {
+        self.bits
+    }
+}
+
+/// A streaming, delta-decoding Golomb reader matching [`GolombRunWriter`].
+pub struct GolombRunReader<E: Endianness, B: GolombRead<E>> {
+    bits: B,
+    b: u64,
+    log2b: u64,
+    max_little_value: u64,
+    last: u64,
+    _marker: core::marker::PhantomData<E>,
+}
+
+/// The result of reading one slot of a [`GolombRunReader`].
+pub enum RunValue {
+    /// The next value in the run.
+    Value(u64),
+    /// The reserved "no value / end of run" escape marker.
+    Escape,
+}
+
+impl<E: Endianness, B: GolombRead<E>> GolombRunReader<E, B> {
+    /// Creates a new reader with modulo `b`, wrapping `bits`.
+    pub fn new(bits: B, b: u64) -> Self {
+        let log2b = (u64::BITS - (b - 1).leading_zeros()) as u64;
+        Self {
+            bits,
+            b,
+            log2b,
+            max_little_value: (1 << log2b) - b,
+            last: 0,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Reads the next slot of the run, which is either the next value
+    /// or the reserved escape marker written by
+    /// [`GolombRunWriter::push_escape`].
+    pub fn next(&mut self) -> Result<RunValue, B::Error> {
+        let quotient = self.bits.read_unary()?;
+        let remainder = self.read_little()?;
+        if quotient == 0 && remainder == 0 {
+            return Ok(RunValue::Escape);
+        }
+        let coded = quotient * self.b + remainder;
+        self.last += coded - 1;
+        Ok(RunValue::Value(self.last))
+    }
+
+    /// Inverts [`GolombRunWriter::write_little`] using the precomputed
+    /// `log2b`/`max_little_value`.
+    ///
+    /// `b == 1` (`log2b == 0`) reads no bits at all: `r` is always `0`.
+    #[inline]
+    fn read_little(&mut self) -> Result<u64, B::Error> {
+        if self.log2b == 0 {
+            return Ok(0);
+        }
+        let v = self.bits.read_bits((self.log2b - 1) as usize)?;
+        if v < self.max_little_value {
+            Ok(v)
+        } else {
+            let bit = self.bits.read_bits(1)?;
+            Ok((v << 1 | bit) - self.max_little_value)
+        }
+    }
+
+    /// Returns the wrapped reader, consuming `self`.
+    pub fn into_inner(self) -> B {
+        self.bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::impls::{BufBitReader, BufBitWriter, MemWordReader, MemWordWriterVec};
+    use crate::traits::{BE, LE};
+    use alloc::vec::Vec;
+
+    /// Pushes `values` (already sorted, possibly with repeats) as a run,
+    /// interleaving an escape marker after every third value, and checks
+    /// that reading the run back reproduces both the values and the
+    /// escape markers in the same order.
+    fn run_roundtrip<E: Endianness>(b: u64, values: &[u64]) {
+        let writer = BufBitWriter::<E, _>::new(MemWordWriterVec::new(Vec::<u64>::new()));
+        let mut run_writer = GolombRunWriter::new(writer, b);
+        for (i, &v) in values.iter().enumerate() {
+            if i != 0 && i % 3 == 0 {
+                run_writer.push_escape().unwrap();
+            }
+            run_writer.push(v).unwrap();
+        }
+        run_writer.push_escape().unwrap();
+        let buffer = run_writer.into_inner().into_inner().unwrap().into_inner();
+
+        let reader = BufBitReader::<E, _>::new(MemWordReader::new(buffer));
+        let mut run_reader = GolombRunReader::new(reader, b);
+        for (i, &v) in values.iter().enumerate() {
+            if i != 0 && i % 3 == 0 {
+                match run_reader.next().unwrap() {
+                    RunValue::Escape => {}
+                    RunValue::Value(got) => panic!("expected escape marker, got {got}"),
+                }
+            }
+            match run_reader.next().unwrap() {
+                RunValue::Value(got) => assert_eq!(got, v, "b={b}"),
+                RunValue::Escape => panic!("unexpected escape marker for value {v}, b={b}"),
+            }
+        }
+        match run_reader.next().unwrap() {
+            RunValue::Escape => {}
+            RunValue::Value(got) => panic!("expected trailing escape marker, got {got}"),
+        }
+    }
+
+    fn run_roundtrip_all_moduli<E: Endianness>() {
+        let values = [0u64, 0, 3, 3, 10, 10, 10, 1_000];
+        // b == 1 forces log2b == 0, the degenerate "no bits at all" path;
+        // the rest cover both power-of-two and non-power-of-two moduli.
+        for &b in &[1u64, 2, 3, 4, 5, 7, 8, 16, 17] {
+            run_roundtrip::<E>(b, &values);
+        }
+    }
+
+    #[test]
+    fn test_run_roundtrip_be() {
+        run_roundtrip_all_moduli::<BE>();
+    }
+
+    #[test]
+    fn test_run_roundtrip_le() {
+        run_roundtrip_all_moduli::<LE>();
+    }
+}
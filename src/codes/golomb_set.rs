@@ -0,0 +1,336 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Golomb-coded sets (GCS).
+//!
+//! A Golomb-coded set is a compact, immutable approximate-membership
+//! structure: smaller than a Bloom filter for the same false-positive
+//! rate, at the cost of being read sequentially rather than probed with
+//! a handful of independent bit lookups. The structure is the one
+//! popularized by the `golomb-set` crate and by Bitcoin's [BIP158 block
+//! filters](https://github.com/bitcoin/bips/blob/master/bip-0158.mediawiki).
+//!
+//! Each of the `n` items is hashed with a keyed 64-bit hash and mapped
+//! uniformly into `[0, n·p)` using the multiply-high trick: a uniform
+//! `x ∈ [0, 2⁶⁴)` is mapped to a uniform value in `[0, range)` by
+//! computing `(x as u128 * range as u128) >> 64`, which avoids the bias
+//! that a modulo reduction would introduce. The resulting values are
+//! sorted, and the first value followed by the successive deltas is
+//! written with [`write_golomb`](super::golomb::GolombWrite::write_golomb)
+//! using modulo `p`, so that the expected code length matches the set's
+//! information content (about `log2(p)` bits per item). Querying
+//! re-hashes and maps the target value, then streams the deltas with
+//! [`read_golomb`](super::golomb::GolombRead::read_golomb), reconstructing
+//! cumulative values and stopping as soon as the running sum meets or
+//! exceeds the target.
+//!
+//! False negatives never occur; false positives occur with probability
+//! about `1 / p`.
+//!
+//! [`GcsBuilder::build`] writes into a caller-owned bit writer and hands
+//! back the [`GcsMeta`] needed to query the set; it does not try to turn
+//! that writer back into a reader itself, since the two are in general
+//! unrelated types. Once the caller has flushed the writer and obtained
+//! a bit reader positioned wherever the GCS begins in its stream (for
+//! instance by wrapping the writer's backing storage in a reader, or by
+//! seeking past other data in a shared stream), [`Gcs::new`] records
+//! that position and pairs the reader with the metadata into a
+//! queryable [`Gcs`]; [`Gcs::contains`] seeks back to the recorded
+//! position before every lookup, so a `Gcs` can be queried repeatedly
+//! even when its bits are embedded after other data (the common
+//! BIP158 concatenated-filter case).
+
+use super::golomb::{GolombRead, GolombWrite};
+use crate::traits::*;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+/// Maps a uniform `u64` into `[0, range)` without modulo bias, using the
+/// multiply-high trick described in the [module documentation](self).
+#[must_use]
+#[inline]
+fn map_into_range(x: u64, range: u64) -> u64 {
+    ((x as u128 * range as u128) >> 64) as u64
+}
+
+/// Hashes `item` keyed by `seed` with `hasher`'s algorithm, so that two
+/// [`Gcs`]s built with different seeds map the same item to independent
+/// values.
+///
+/// Takes the hasher as a `H: Hasher + Default` type parameter, rather
+/// than reaching for `std::collections::hash_map::DefaultHasher`, so
+/// that this module stays usable without the `std` feature: callers in
+/// a `no_std` context supply their own `Hasher` (e.g. from `twox-hash`
+/// or `siphasher`).
+#[inline]
+fn keyed_hash<T: Hash, H: Hasher + Default>(item: &T, seed: u64) -> u64 {
+    let mut hasher = H::default();
+    seed.hash(&mut hasher);
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A builder that accumulates items and serializes them into a [`Gcs`].
+///
+/// The false-positive parameter `p` is also used, per the [module
+/// documentation](self), as the modulo `b` of the Golomb code, so that
+/// the expected code length matches the set's information content.
+///
+/// `H` is the [`Hasher`] used to key items into the set; it must match
+/// the `H` later passed to [`Gcs::contains`]. With the `std` feature
+/// enabled, [`GcsBuilder::new_default_hasher`] picks
+/// `std::collections::hash_map::DefaultHasher` for convenience.
+pub struct GcsBuilder<T, H> {
+    p: u64,
+    seed: u64,
+    items: Vec<T>,
+    _hasher: core::marker::PhantomData<H>,
+}
+
+impl<T: Hash, H: Hasher + Default> GcsBuilder<T, H> {
+    /// Creates a new builder for a set with false-positive parameter `p`
+    /// (a query on an item not in the set returns `true` with
+    /// probability about `1 / p`) keyed with `seed`, hashing items with
+    /// `H`.
+    pub fn new(p: u64, seed: u64) -> Self {
+        Self {
+            p,
+            seed,
+            items: Vec::new(),
+            _hasher: core::marker::PhantomData,
+        }
+    }
+
+    /// Adds `item` to the set being built.
+    pub fn push(&mut self, item: T) -> &mut Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Hashes, sorts, and delta-Golomb-codes the accumulated items into
+    /// `bits`, returning the [`GcsMeta`] needed to query the resulting
+    /// set once `bits` has been turned into a bit reader (see the
+    /// [module documentation](self)).
+    pub fn build<E: Endianness, B: GolombWrite<E>>(
+        self,
+        bits: &mut B,
+    ) -> Result<GcsMeta, B::Error> {
+        let n = self.items.len() as u64;
+        let range = n * self.p;
+
+        let mut values = self
+            .items
+            .iter()
+            .map(|item| map_into_range(keyed_hash::<_, H>(item, self.seed), range))
+            .collect::<Vec<_>>();
+        values.sort_unstable();
+
+        let mut prev = 0u64;
+        for &value in values.iter() {
+            bits.write_golomb(value - prev, self.p)?;
+            prev = value;
+        }
+
+        Ok(GcsMeta {
+            n,
+            p: self.p,
+            seed: self.seed,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Hash> GcsBuilder<T, std::collections::hash_map::DefaultHasher> {
+    /// Creates a new builder hashing items with
+    /// `std::collections::hash_map::DefaultHasher`, for callers that
+    /// don't need control over the hashing algorithm.
+    pub fn new_default_hasher(p: u64, seed: u64) -> Self {
+        Self::new(p, seed)
+    }
+}
+
+/// The metadata of a Golomb-coded set, as produced by
+/// [`GcsBuilder::build`] and consumed by [`Gcs::new`].
+///
+/// Stores the number of items `n`, the false-positive parameter `p`, and
+/// the seed used to key the hash function; none of these can be
+/// recovered from the bitstream alone, so they must be carried
+/// alongside it.
+#[derive(Debug, Clone, Copy)]
+pub struct GcsMeta {
+    n: u64,
+    p: u64,
+    seed: u64,
+}
+
+/// A Golomb-coded set, pairing a bit reader with the [`GcsMeta`] returned
+/// by [`GcsBuilder::build`] and the bit offset at which the GCS begins in
+/// that reader's stream.
+///
+/// The start offset is recorded rather than assumed to be `0`, so a GCS
+/// embedded after other data in a shared stream (for instance several
+/// BIP158-style filters concatenated back to back) can still be queried
+/// correctly: [`contains`](Self::contains) seeks back to it before every
+/// lookup instead of seeking to the absolute start of the stream.
+pub struct Gcs<B, H> {
+    meta: GcsMeta,
+    start: u64,
+    bits: B,
+    _hasher: core::marker::PhantomData<H>,
+}
+
+impl<B, H> Gcs<B, H> {
+    /// Returns the number of items the set was built from.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.meta.n
+    }
+
+    /// Returns whether the set is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.meta.n == 0
+    }
+
+    /// Returns the false-positive parameter the set was built with.
+    #[must_use]
+    pub fn p(&self) -> u64 {
+        self.meta.p
+    }
+}
+
+impl<E: Endianness, B: BitSeek<E>, H> Gcs<B, H> {
+    /// Pairs `bits`, a bit reader already positioned at the start of the
+    /// bitstream written by [`GcsBuilder::build`], with the `meta` it
+    /// returned, recording the reader's current bit position as the
+    /// offset [`contains`](Self::contains) will seek back to.
+    pub fn new(bits: B, meta: GcsMeta) -> Result<Self, B::Error> {
+        let start = bits.get_bit_pos()?;
+        Ok(Self {
+            meta,
+            start,
+            bits,
+            _hasher: core::marker::PhantomData,
+        })
+    }
+}
+
+impl<E: Endianness, B: GolombRead<E> + BitSeek<E>, H: Hasher + Default> Gcs<B, H> {
+    /// Returns whether `item` is (probably) a member of the set.
+    ///
+    /// False negatives never occur; false positives occur with
+    /// probability about `1 / p`.
+    pub fn contains<T: Hash>(&mut self, item: &T) -> Result<bool, B::Error> {
+        let range = self.meta.n * self.meta.p;
+        let target = map_into_range(keyed_hash::<_, H>(item, self.meta.seed), range);
+
+        self.bits.set_bit_pos(self.start)?;
+        let mut cumulative = 0u64;
+        for _ in 0..self.meta.n {
+            cumulative += self.bits.read_golomb(self.meta.p)?;
+            if cumulative >= target {
+                return Ok(cumulative == target);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::impls::{BufBitReader, BufBitWriter, MemWordReader, MemWordWriterVec};
+    use crate::traits::{BE, LE};
+
+    /// A [`Hasher`] good enough for tests without depending on the
+    /// optional `std` feature: an FNV-1a variant.
+    #[derive(Default)]
+    struct FnvHasher(u64);
+
+    impl Hasher for FnvHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            let mut hash = if self.0 == 0 { 0xcbf2_9ce4_8422_2325 } else { self.0 };
+            for &byte in bytes {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+            self.0 = hash;
+        }
+
+        fn finish(&self) -> u64 {
+            self.0
+        }
+    }
+
+    fn gcs_roundtrip_no_false_negatives<E: Endianness>() {
+        let items: Vec<u64> = (0..500u64).collect();
+        let p = 16;
+        let seed = 42;
+
+        let mut builder = GcsBuilder::<_, FnvHasher>::new(p, seed);
+        for &item in &items {
+            builder.push(item);
+        }
+
+        let mut writer = BufBitWriter::<E, _>::new(MemWordWriterVec::new(Vec::<u64>::new()));
+        let meta = builder.build(&mut writer).unwrap();
+        let buffer = writer.into_inner().unwrap().into_inner();
+
+        let reader = BufBitReader::<E, _>::new(MemWordReader::new(buffer));
+        let mut gcs = Gcs::<_, FnvHasher>::new(reader, meta).unwrap();
+
+        for item in &items {
+            assert!(gcs.contains(item).unwrap(), "false negative for {item}");
+        }
+    }
+
+    #[test]
+    fn test_gcs_roundtrip_no_false_negatives_be() {
+        gcs_roundtrip_no_false_negatives::<BE>();
+    }
+
+    #[test]
+    fn test_gcs_roundtrip_no_false_negatives_le() {
+        gcs_roundtrip_no_false_negatives::<LE>();
+    }
+
+    /// Builds a GCS with some unrelated bits written before it in the
+    /// same stream, so `Gcs::new` must record a non-zero start offset
+    /// for `contains` to decode correctly.
+    fn gcs_roundtrip_with_leading_padding<E: Endianness>() {
+        let items: Vec<u64> = (0..200u64).collect();
+        let p = 16;
+        let seed = 7;
+
+        let mut builder = GcsBuilder::<_, FnvHasher>::new(p, seed);
+        for &item in &items {
+            builder.push(item);
+        }
+
+        let mut writer = BufBitWriter::<E, _>::new(MemWordWriterVec::new(Vec::<u64>::new()));
+        writer.write_unary(123).unwrap();
+        let meta = builder.build(&mut writer).unwrap();
+        let buffer = writer.into_inner().unwrap().into_inner();
+
+        let mut reader = BufBitReader::<E, _>::new(MemWordReader::new(buffer));
+        assert_eq!(reader.read_unary().unwrap(), 123);
+        let mut gcs = Gcs::<_, FnvHasher>::new(reader, meta).unwrap();
+
+        for item in &items {
+            assert!(gcs.contains(item).unwrap(), "false negative for {item}");
+        }
+    }
+
+    #[test]
+    fn test_gcs_roundtrip_with_leading_padding_be() {
+        gcs_roundtrip_with_leading_padding::<BE>();
+    }
+
+    #[test]
+    fn test_gcs_roundtrip_with_leading_padding_le() {
+        gcs_roundtrip_with_leading_padding::<LE>();
+    }
+}